@@ -0,0 +1,164 @@
+use crate::AppState;
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use axum_sessions::extractors::WritableSession;
+use serde::Deserialize;
+use sqlx::Row;
+
+const ADMIN_SESSION_KEY: &str = "admin_authenticated";
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/login", post(login))
+        .route("/admin/logout", post(logout))
+        .route("/admin/export", get(export))
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    password: String,
+}
+
+async fn login(
+    State(state): State<AppState>,
+    mut session: WritableSession,
+    Json(payload): Json<LoginRequest>,
+) -> StatusCode {
+    match verify_admin_password(&state.admin_password_hash, &payload.password) {
+        Ok(true) => {
+            session
+                .insert(ADMIN_SESSION_KEY, true)
+                .expect("Failed to write session");
+            StatusCode::OK
+        }
+        Ok(false) => StatusCode::UNAUTHORIZED,
+        Err(()) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Verifies `password` against the PHC-formatted argon2 `hash`.
+///
+/// Returns `Err(())` if `hash` itself isn't a valid PHC string (a
+/// misconfiguration), as distinct from a merely wrong password.
+fn verify_admin_password(hash: &str, password: &str) -> Result<bool, ()> {
+    let parsed = PasswordHash::new(hash).map_err(|_| ())?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+async fn logout(mut session: WritableSession) -> StatusCode {
+    session.destroy();
+    StatusCode::OK
+}
+
+fn is_authenticated(session: &WritableSession) -> bool {
+    session.get::<bool>(ADMIN_SESSION_KEY).unwrap_or(false)
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+#[derive(Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+async fn export(
+    State(state): State<AppState>,
+    session: WritableSession,
+    axum::extract::Query(query): axum::extract::Query<ExportQuery>,
+) -> Response {
+    if !is_authenticated(&session) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let rows = match sqlx::query("SELECT encrypted, captcha FROM answers")
+        .fetch_all(&state.pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("{}", e)).into_response()
+        }
+    };
+
+    match query.format {
+        ExportFormat::Json => {
+            let answers: Vec<_> = rows
+                .iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "encrypted": row.get::<String, _>("encrypted"),
+                        "captcha": row.get::<String, _>("captcha"),
+                    })
+                })
+                .collect();
+            Json(answers).into_response()
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record(["encrypted", "captcha"])
+                .expect("Failed to write CSV header");
+            for row in &rows {
+                writer
+                    .write_record([
+                        row.get::<String, _>("encrypted"),
+                        row.get::<String, _>("captcha"),
+                    ])
+                    .expect("Failed to write CSV row");
+            }
+            let csv = writer.into_inner().expect("Failed to flush CSV writer");
+            ([("content-type", "text/csv")], csv).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::password_hash::SaltString;
+    use argon2::PasswordHasher;
+    use rand_core::OsRng;
+
+    fn hash_password(password: &str) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("Failed to hash password")
+            .to_string()
+    }
+
+    #[test]
+    fn verify_admin_password_accepts_the_correct_password() {
+        let hash = hash_password("correct horse battery staple");
+        assert_eq!(
+            verify_admin_password(&hash, "correct horse battery staple"),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn verify_admin_password_rejects_the_wrong_password() {
+        let hash = hash_password("correct horse battery staple");
+        assert_eq!(verify_admin_password(&hash, "wrong password"), Ok(false));
+    }
+
+    #[test]
+    fn verify_admin_password_reports_a_malformed_hash() {
+        assert_eq!(verify_admin_password("not a PHC string", "anything"), Err(()));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CHALLENGE_TTL_SECONDS: u64 = 300;
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new().route("/captcha", get(issue_challenge))
+}
+
+#[derive(Serialize, Deserialize)]
+struct CaptchaClaims {
+    exp: u64,
+    /// Unique token id, recorded on use to prevent replay.
+    jti: String,
+    /// Expected answer to the challenge, e.g. "7" for "3 + 4".
+    answer: String,
+}
+
+#[derive(Serialize)]
+struct Challenge {
+    question: String,
+    token: String,
+}
+
+async fn issue_challenge(State(state): State<AppState>) -> Json<Challenge> {
+    let mut rng = rand::thread_rng();
+    let a: u32 = rng.gen_range(1..10);
+    let b: u32 = rng.gen_range(1..10);
+
+    let claims = CaptchaClaims {
+        exp: now() + CHALLENGE_TTL_SECONDS,
+        jti: random_jti(&mut rng),
+        answer: (a + b).to_string(),
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(state.captcha_secret.as_bytes()),
+    )
+    .expect("Failed to sign captcha token");
+
+    Json(Challenge {
+        question: format!("What is {a} + {b}?"),
+        token,
+    })
+}
+
+/// Why this lives here instead of in `store_form`: it's the one choke point
+/// every submission's captcha must pass through, so rejection reasons are
+/// reported consistently regardless of what's wrong with the token.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum CaptchaError {
+    Missing,
+    Expired,
+    AlreadyUsed,
+    WrongAnswer,
+}
+
+impl CaptchaError {
+    pub(crate) fn status_code(&self) -> StatusCode {
+        match self {
+            CaptchaError::Missing => StatusCode::BAD_REQUEST,
+            CaptchaError::Expired => StatusCode::BAD_REQUEST,
+            CaptchaError::AlreadyUsed => StatusCode::TOO_MANY_REQUESTS,
+            CaptchaError::WrongAnswer => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    pub(crate) fn metric_reason(&self) -> &'static str {
+        match self {
+            CaptchaError::Missing => "missing",
+            CaptchaError::Expired => "expired",
+            CaptchaError::AlreadyUsed => "replayed",
+            CaptchaError::WrongAnswer => "wrong_answer",
+        }
+    }
+}
+
+/// A genuine database failure while verifying a captcha token, as distinct
+/// from the token itself being rejected. Callers should surface this as a
+/// `5xx` and must not count it towards the captcha-rejection metric, since
+/// it says nothing about whether the token or answer were valid.
+pub(crate) struct VerifyDbError(pub(crate) sqlx::Error);
+
+/// Verifies `token` against `answer`, records its `jti` as spent so the same
+/// challenge can't be redeemed twice, and returns that `jti` for callers that
+/// want an audit trail of which challenge a submission answered.
+pub(crate) async fn verify(
+    state: &AppState,
+    token: &str,
+    answer: &str,
+) -> Result<Result<String, CaptchaError>, VerifyDbError> {
+    if token.is_empty() {
+        return Ok(Err(CaptchaError::Missing));
+    }
+
+    // The same server issues and checks these tokens, so there is no clock
+    // skew to allow for: a token is expired as soon as `exp` has passed.
+    let mut validation = Validation::default();
+    validation.leeway = 0;
+
+    let claims = match decode::<CaptchaClaims>(
+        token,
+        &DecodingKey::from_secret(state.captcha_secret.as_bytes()),
+        &validation,
+    ) {
+        Ok(token_data) => token_data.claims,
+        Err(_) => return Ok(Err(CaptchaError::Expired)),
+    };
+
+    if claims.answer != answer {
+        return Ok(Err(CaptchaError::WrongAnswer));
+    }
+
+    let inserted = sqlx::query("INSERT OR IGNORE INTO used_captcha_tokens(jti) VALUES (?)")
+        .bind(&claims.jti)
+        .execute(&state.pool)
+        .await
+        .map_err(VerifyDbError)?;
+
+    if inserted.rows_affected() == 0 {
+        return Ok(Err(CaptchaError::AlreadyUsed));
+    }
+
+    Ok(Ok(claims.jti))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the Unix epoch")
+        .as_secs()
+}
+
+fn random_jti(rng: &mut impl Rng) -> String {
+    let bytes: [u8; 16] = rng.gen();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+    use std::sync::Arc;
+
+    const SECRET: &str = "test-captcha-secret";
+
+    async fn test_state() -> AppState {
+        AppState {
+            pool: crate::db::connect(":memory:")
+                .await
+                .expect("Failed to set up in-memory test DB"),
+            admin_password_hash: String::new(),
+            captcha_secret: SECRET.to_string(),
+            metrics: Arc::new(Metrics::new()),
+        }
+    }
+
+    fn token_for(claims: &CaptchaClaims) -> String {
+        encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(SECRET.as_bytes()),
+        )
+        .expect("Failed to sign test token")
+    }
+
+    #[tokio::test]
+    async fn verify_accepts_a_fresh_correct_token() {
+        let state = test_state().await;
+        let claims = CaptchaClaims {
+            exp: now() + CHALLENGE_TTL_SECONDS,
+            jti: "jti-1".to_string(),
+            answer: "7".to_string(),
+        };
+        let token = token_for(&claims);
+
+        assert_eq!(
+            verify(&state, &token, "7").await.ok(),
+            Some(Ok("jti-1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_an_empty_token() {
+        let state = test_state().await;
+        assert_eq!(
+            verify(&state, "", "7").await.ok(),
+            Some(Err(CaptchaError::Missing))
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_the_wrong_answer() {
+        let state = test_state().await;
+        let claims = CaptchaClaims {
+            exp: now() + CHALLENGE_TTL_SECONDS,
+            jti: "jti-2".to_string(),
+            answer: "7".to_string(),
+        };
+        let token = token_for(&claims);
+
+        assert_eq!(
+            verify(&state, &token, "8").await.ok(),
+            Some(Err(CaptchaError::WrongAnswer))
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_an_expired_token() {
+        let state = test_state().await;
+        let claims = CaptchaClaims {
+            exp: now() - 1,
+            jti: "jti-3".to_string(),
+            answer: "7".to_string(),
+        };
+        let token = token_for(&claims);
+
+        assert_eq!(
+            verify(&state, &token, "7").await.ok(),
+            Some(Err(CaptchaError::Expired))
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_a_replayed_token() {
+        let state = test_state().await;
+        let claims = CaptchaClaims {
+            exp: now() + CHALLENGE_TTL_SECONDS,
+            jti: "jti-4".to_string(),
+            answer: "7".to_string(),
+        };
+        let token = token_for(&claims);
+
+        assert_eq!(
+            verify(&state, &token, "7").await.ok(),
+            Some(Ok("jti-4".to_string()))
+        );
+        assert_eq!(
+            verify(&state, &token, "7").await.ok(),
+            Some(Err(CaptchaError::AlreadyUsed))
+        );
+    }
+}
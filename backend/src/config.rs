@@ -0,0 +1,113 @@
+use figment::providers::{Env, Format, Serialized, Toml};
+use figment::Figment;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Runtime configuration for the server.
+///
+/// Values are layered, lowest to highest priority:
+/// 1. the defaults below,
+/// 2. `sdcmap.toml` in the current directory (or the path given on the
+///    command line),
+/// 3. `SDCMAP_*` environment variables.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Directory of static files served for any path not otherwise handled.
+    pub serve_path: String,
+    /// TCP port to listen on.
+    pub port: u16,
+    /// Path to the SQLite database file, or `:memory:`.
+    pub db: String,
+    /// Address to bind the HTTP listener to.
+    pub bind_address: IpAddr,
+    /// PHC-formatted argon2 hash of the admin password.
+    pub admin_password_hash: String,
+    /// Secret used to sign and verify captcha challenge tokens.
+    pub captcha_secret: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            serve_path: "static".to_string(),
+            port: 3000,
+            db: ":memory:".to_string(),
+            bind_address: IpAddr::from([127, 0, 0, 1]),
+            admin_password_hash: String::new(),
+            captcha_secret: String::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads configuration from `config_path` (if it exists) and
+    /// `SDCMAP_*` environment variables, falling back to [`Config::default`].
+    ///
+    /// Returns an error describing the problem instead of panicking, so the
+    /// caller can report it and exit cleanly.
+    pub fn load(config_path: &str) -> Result<Config, figment::Error> {
+        let config: Config = Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::file(config_path))
+            .merge(Env::prefixed("SDCMAP_"))
+            .extract()?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks for values that have no safe default and must be supplied by
+    /// the operator, returning a descriptive error instead of panicking.
+    fn validate(&self) -> Result<(), figment::Error> {
+        if self.admin_password_hash.is_empty() {
+            return Err(figment::Error::from(
+                "admin_password_hash must be set (via config file or SDCMAP_ADMIN_PASSWORD_HASH)"
+                    .to_string(),
+            ));
+        }
+
+        if self.captcha_secret.is_empty() {
+            return Err(figment::Error::from(
+                "captcha_secret must be set (via config file or SDCMAP_CAPTCHA_SECRET)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured() -> Config {
+        Config {
+            admin_password_hash: "$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$aGFzaGhhc2g".to_string(),
+            captcha_secret: "some-secret".to_string(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn validate_accepts_a_fully_configured_instance() {
+        assert!(configured().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_a_missing_admin_password_hash() {
+        let config = Config {
+            admin_password_hash: String::new(),
+            ..configured()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_a_missing_captcha_secret() {
+        let config = Config {
+            captcha_secret: String::new(),
+            ..configured()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+/// Opens (creating if necessary) the SQLite database at `db_path` and returns a
+/// connection pool sized to the number of available CPUs.
+///
+/// `db_path` is a plain filesystem path, or `:memory:` for an in-memory
+/// database — not a `sqlite:` URI, so it's built with `SqliteConnectOptions`
+/// setters rather than `FromStr`/`Url::parse`.
+///
+/// WAL mode is enabled so that concurrent readers don't block writers, which
+/// matters once more than one `/submit` request is in flight at a time.
+pub async fn connect(db_path: &str) -> sqlx::Result<SqlitePool> {
+    let options = if db_path == ":memory:" {
+        // Without shared_cache, every pooled connection gets its own private
+        // in-memory database, so a write made on one connection would be
+        // invisible to a read made on another.
+        SqliteConnectOptions::new().in_memory(true).shared_cache(true)
+    } else {
+        SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+    }
+    .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(num_cpus::get() as u32)
+        .connect_with(options)
+        .await?;
+
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS answers (
+            encrypted TEXT,
+            captcha TEXT
+        )
+        ",
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS used_captcha_tokens (
+            jti TEXT PRIMARY KEY
+        )
+        ",
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_writes_are_visible_across_pooled_connections() {
+        let pool = connect(":memory:")
+            .await
+            .expect("Failed to set up in-memory test DB");
+
+        sqlx::query("INSERT INTO answers(encrypted, captcha) VALUES (?, ?)")
+            .bind("cyphertext")
+            .bind("jti")
+            .execute(&pool)
+            .await
+            .expect("Failed to insert via one pooled connection");
+
+        // Acquire a second connection explicitly so the read can't just be
+        // satisfied by the pool handing back the connection that wrote.
+        let mut other_connection = pool
+            .acquire()
+            .await
+            .expect("Failed to acquire a second pooled connection");
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answers")
+            .fetch_one(&mut *other_connection)
+            .await
+            .expect("Failed to read via the second pooled connection");
+
+        assert_eq!(count, 1);
+    }
+}
@@ -1,63 +1,77 @@
+mod admin;
+mod captcha;
+mod config;
+mod db;
+mod metrics;
+mod openapi;
+
+use async_sqlx_session::SqliteSessionStore;
 use axum::{
+    extract::State,
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::post,
     Json, Router,
 };
-use lazy_static::lazy_static;
+use axum_sessions::SessionLayer;
+use config::Config;
+use metrics::Metrics;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use std::net::SocketAddr;
-use std::sync::{Mutex, MutexGuard};
+use std::sync::Arc;
 use tower_http::services::ServeDir;
+use tower_http::trace::TraceLayer;
+use tracing::instrument;
 
-lazy_static! {
-    static ref SQLITE_CONNECTION: Mutex<sqlite::Connection> = {
-        let args: Vec<String> = std::env::args().collect();
-        let len = args.len();
-        if len < 4 {
-            Mutex::new(sqlite::open(":memory:").expect("Failed to create DB in memory"))
-        } else {
-            Mutex::new(sqlite::open(&args[3]).expect("Failed open DB"))
-        }
-    };
+#[derive(Clone)]
+pub(crate) struct AppState {
+    pub(crate) pool: SqlitePool,
+    pub(crate) admin_password_hash: String,
+    pub(crate) captcha_secret: String,
+    pub(crate) metrics: Arc<Metrics>,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
     let args: Vec<String> = std::env::args().collect();
-    let args_len = args.len();
+    let config_path = args.get(1).map(String::as_str).unwrap_or("sdcmap.toml");
 
-    if args_len < 2 {
-        return Err(format!("Usage: {} <path> [port] [db]", args[0]));
-    }
-    let serve_path: &String = &args[1];
-
-    let port: u16 = if args_len < 3 {
-        3000
-    } else {
-        args[2]
-            .parse::<u16>()
-            .unwrap_or_else(|_| panic!("Cannot parse {} as a port number", args[2]))
-    };
+    let config = Config::load(config_path).map_err(|e| format!("Invalid configuration: {e}"))?;
+
+    let pool = db::connect(&config.db)
+        .await
+        .expect("Failed to set up the SQLite connection pool");
+
+    let session_store = SqliteSessionStore::from_client(pool.clone());
+    session_store
+        .migrate()
+        .await
+        .expect("Failed to run session store migrations");
 
-    let query = "
-        CREATE TABLE IF NOT EXISTS answers (
-            encrypted TEXT,
-            captcha TEXT
-        )
-    ";
-    SQLITE_CONNECTION
-        .lock()
-        .expect("Cannot acquire SQL connection")
-        .execute(query)
-        .expect("Failed to create the `answers` table");
+    let mut session_secret = [0u8; 64];
+    rand::thread_rng().fill_bytes(&mut session_secret);
+    let session_layer = SessionLayer::new(session_store, &session_secret);
 
     // build our application with a route
-    let app: Router = router(serve_path);
+    let app: Router = router(
+        &config.serve_path,
+        pool,
+        config.admin_password_hash.clone(),
+        config.captcha_secret.clone(),
+    )
+    .layer(session_layer)
+    .layer(TraceLayer::new_for_http());
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
-    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let addr = SocketAddr::from((config.bind_address, config.port));
+    tracing::info!(%addr, "starting server");
     let builder = axum::Server::bind(&addr);
 
     builder
@@ -68,17 +82,72 @@ async fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn router(serve_path: &String) -> Router {
+fn router(
+    serve_path: &str,
+    pool: SqlitePool,
+    admin_password_hash: String,
+    captcha_secret: String,
+) -> Router {
     Router::new()
-        .route(
-            "/submit",
-            post(|payload| async { handle_sqlite_error(store_form(payload).await) }),
-        )
+        .route("/submit", post(submit))
+        .merge(admin::router())
+        .merge(captcha::router())
+        .merge(metrics::router())
+        .merge(openapi::router())
         .fallback_service(ServeDir::new(serve_path))
+        .with_state(AppState {
+            pool,
+            admin_password_hash,
+            captcha_secret,
+            metrics: Arc::new(Metrics::new()),
+        })
+}
+
+/// Stores an encrypted answer submission, gated by a prior `/captcha` challenge.
+#[utoipa::path(
+    post,
+    path = "/submit",
+    request_body = Input,
+    responses(
+        (status = 201, description = "Submission stored", body = String),
+        (status = 400, description = "Captcha token missing, expired, or answered incorrectly"),
+        (status = 429, description = "Captcha token already used"),
+        (status = 500, description = "Database error while storing the submission"),
+    ),
+    tag = "submit",
+)]
+pub(crate) async fn submit(
+    State(state): State<AppState>,
+    payload: Json<Input>,
+) -> (StatusCode, Response) {
+    let _timer = state.metrics.submit_duration_seconds.start_timer();
+
+    let jti = match captcha::verify(&state, &payload.captcha_token, &payload.captcha_answer).await
+    {
+        Ok(Ok(jti)) => jti,
+        Ok(Err(e)) => {
+            state
+                .metrics
+                .captcha_rejections_total
+                .with_label_values(&[e.metric_reason()])
+                .inc();
+            return (e.status_code(), e.metric_reason().into_response());
+        }
+        Err(captcha::VerifyDbError(e)) => {
+            state.metrics.db_errors_total.inc();
+            tracing::error!(error = %e, "failed to verify captcha token");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{}", e).into_response(),
+            );
+        }
+    };
+
+    handle_sqlite_error(store_form(&state.pool, &state.metrics, &jti, payload).await)
 }
 
 fn handle_sqlite_error(
-    value: sqlite::Result<(StatusCode, impl IntoResponse)>,
+    value: sqlx::Result<(StatusCode, impl IntoResponse)>,
 ) -> (StatusCode, Response) {
     match value {
         Ok((code, msg)) => (code, msg.into_response()),
@@ -89,27 +158,44 @@ fn handle_sqlite_error(
     }
 }
 
-async fn store_form(Json(payload): Json<Input>) -> sqlite::Result<(StatusCode, &'static str)> {
-    let insert_query = "
-        INSERT INTO answers(encrypted, captcha)
-        VALUES (:encrypted, :captcha)
-    ";
-    let connection: MutexGuard<sqlite::Connection> = SQLITE_CONNECTION
-        .lock()
-        .expect("Cannot acquire SQL connection");
-    let mut statement: sqlite::Statement = connection.prepare(insert_query)?;
-    statement.bind::<&[(_, sqlite::Value)]>(&[
-        (":encrypted", payload.encrypted.into()),
-        (":captcha", payload.captcha.into()),
-    ])?;
-    while statement.next()? != sqlite::State::Done {}
+#[instrument(skip(pool, metrics, jti, payload))]
+async fn store_form(
+    pool: &SqlitePool,
+    metrics: &Metrics,
+    jti: &str,
+    Json(payload): Json<Input>,
+) -> sqlx::Result<(StatusCode, &'static str)> {
+    let result = sqlx::query("INSERT INTO answers(encrypted, captcha) VALUES (?, ?)")
+        .bind(&payload.encrypted)
+        .bind(jti)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = &result {
+        metrics.db_errors_total.inc();
+        tracing::error!(error = %e, "failed to store submission");
+    }
+    result?;
 
+    metrics.submissions_total.inc();
     Result::Ok((StatusCode::CREATED, "Saved!"))
 }
 
-// the input to our `create_user` handler
-#[derive(Serialize, Deserialize, Debug)]
-struct Input {
+/// The body of a `/submit` request.
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+pub(crate) struct Input {
+    /// Client-side-encrypted answer payload.
+    ///
+    /// Encoded as base64 (standard alphabet, padded) ciphertext produced by
+    /// the frontend's encryption of the answer JSON, so the server never
+    /// sees plaintext answers. Third-party clients must match this
+    /// encoding/encryption scheme for their submissions to be readable by
+    /// the `/admin/export` consumer.
+    #[schema(example = "U2FsdGVkX1+3n9q5...")]
     encrypted: String,
-    captcha: String,
+    /// JWT issued by `GET /captcha`, identifying the challenge being answered.
+    captcha_token: String,
+    /// The user's answer to the challenge named by `captcha_token`.
+    #[schema(example = "7")]
+    captcha_answer: String,
 }
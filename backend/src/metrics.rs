@@ -0,0 +1,83 @@
+use axum::routing::get;
+use axum::Router;
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+
+use crate::AppState;
+
+/// Prometheus counters and histograms for the submission pipeline.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    registry: Registry,
+    pub(crate) submissions_total: IntCounter,
+    pub(crate) db_errors_total: IntCounter,
+    pub(crate) captcha_rejections_total: IntCounterVec,
+    pub(crate) submit_duration_seconds: Histogram,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Metrics {
+        let registry = Registry::new();
+
+        let submissions_total = IntCounter::with_opts(Opts::new(
+            "sdcmap_submissions_total",
+            "Total number of successfully stored submissions",
+        ))
+        .expect("Failed to create submissions_total counter");
+
+        let db_errors_total = IntCounter::with_opts(Opts::new(
+            "sdcmap_db_errors_total",
+            "Total number of database errors while handling submissions",
+        ))
+        .expect("Failed to create db_errors_total counter");
+
+        let captcha_rejections_total = IntCounterVec::new(
+            Opts::new(
+                "sdcmap_captcha_rejections_total",
+                "Total number of submissions rejected by captcha verification, by reason",
+            ),
+            &["reason"],
+        )
+        .expect("Failed to create captcha_rejections_total counter");
+
+        let submit_duration_seconds = Histogram::with_opts(HistogramOpts::new(
+            "sdcmap_submit_duration_seconds",
+            "Latency of the /submit handler",
+        ))
+        .expect("Failed to create submit_duration_seconds histogram");
+
+        registry
+            .register(Box::new(submissions_total.clone()))
+            .expect("Failed to register submissions_total");
+        registry
+            .register(Box::new(db_errors_total.clone()))
+            .expect("Failed to register db_errors_total");
+        registry
+            .register(Box::new(captcha_rejections_total.clone()))
+            .expect("Failed to register captcha_rejections_total");
+        registry
+            .register(Box::new(submit_duration_seconds.clone()))
+            .expect("Failed to register submit_duration_seconds");
+
+        Metrics {
+            registry,
+            submissions_total,
+            db_errors_total,
+            captcha_rejections_total,
+            submit_duration_seconds,
+        }
+    }
+}
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new().route("/metrics", get(serve_metrics))
+}
+
+async fn serve_metrics(axum::extract::State(state): axum::extract::State<AppState>) -> String {
+    let metric_families = state.metrics.registry.gather();
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&metric_families, &mut buffer)
+        .expect("Failed to encode metrics");
+    String::from_utf8(buffer).expect("Metrics output is not valid UTF-8")
+}
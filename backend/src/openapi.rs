@@ -0,0 +1,19 @@
+use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{AppState, Input};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(crate::submit),
+    components(schemas(Input)),
+    tags(
+        (name = "submit", description = "Submission ingestion endpoint")
+    )
+)]
+pub(crate) struct ApiDoc;
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new().merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
+}